@@ -3,29 +3,17 @@
 #![no_std]
 #![deny(warnings, missing_docs)]
 
-use core::{cell::UnsafeCell, mem::size_of, num::NonZeroU32};
+use core::{
+    cell::UnsafeCell,
+    marker::PhantomData,
+    mem::{align_of, size_of},
+    num::NonZeroU32,
+};
 
-/// See §1.
-const COUNT_SOURCE: usize = 1024;
-/// See §1.
-const COUNT_CONTEXT: usize = 15872;
 const U32_BITS: usize = u32::BITS as _;
-
-#[repr(transparent)]
-struct Priorities([UnsafeCell<u32>; COUNT_SOURCE]);
-
-#[repr(transparent)]
-struct PendingBits([UnsafeCell<u32>; COUNT_SOURCE / U32_BITS]);
-
-#[repr(transparent)]
-struct Enables([UnsafeCell<u32>; COUNT_SOURCE * COUNT_CONTEXT / U32_BITS]);
-
-#[repr(C, align(4096))]
-struct ContextLocal {
-    priority_threshold: UnsafeCell<u32>,
-    claim_or_completion: UnsafeCell<u32>,
-    _reserved: [u8; 4096 - 2 * size_of::<u32>()],
-}
+/// Number of 32-bit words the PLIC memory map reserves for one context's enable bits (`0x80`
+/// bytes), fixed by §6 regardless of how many sources an implementation actually wires up.
+const ENABLE_WORDS_PER_CONTEXT: usize = 0x80 / size_of::<u32>();
 
 /// Trait for enums of external interrupt source.
 ///
@@ -71,20 +59,119 @@ impl HartContext for usize {
     }
 }
 
-/// The PLIC memory mapping.
+/// Privilege mode of a [`HartPriv`] context.
+///
+/// See §1.1.
+#[cfg(feature = "hart-priv")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Privilege {
+    /// Machine mode.
+    Machine,
+    /// Supervisor mode.
+    Supervisor,
+}
+
+/// Number of PLIC contexts [`HartPriv`] reserves per hart: one for machine mode, one for
+/// supervisor mode.
+#[cfg(feature = "hart-priv")]
+pub const CONTEXTS_PER_HART: usize = 2;
+
+/// The dominant hart/privilege-to-context convention (QEMU virt, Spike, and most SiFive parts):
+/// `context = hart * 2 + (mode == Supervisor)`, with machine mode even and supervisor mode odd.
+///
+/// Context organization is vendor-defined by the PLIC specification (see §1.1), so this is not
+/// universal, but it is common enough that trap handlers targeting it can write
+/// `plic.claim(HartPriv { hart, mode: Privilege::Supervisor })` directly instead of precomputing
+/// the raw context index. Implementations that deviate from this convention should keep the
+/// `hart-priv` feature disabled and implement [`HartContext`] for their own type instead.
+#[cfg(feature = "hart-priv")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HartPriv {
+    /// The hart id.
+    pub hart: usize,
+    /// The privilege mode this context serves.
+    pub mode: Privilege,
+}
+
+#[cfg(feature = "hart-priv")]
+impl HartContext for HartPriv {
+    #[inline]
+    fn index(self) -> usize {
+        self.hart * CONTEXTS_PER_HART + (self.mode == Privilege::Supervisor) as usize
+    }
+}
+
+/// The PLIC memory mapping, parameterized over `SOURCES` interrupt sources and `CONTEXTS` hart
+/// contexts.
+///
+/// The RISC-V PLIC specification fixes the register layout's byte offsets (priorities at `0`,
+/// pending bits at `0x1000`, per-context enable bits at `0x2000`, per-context threshold and
+/// claim/complete registers at `0x20_0000`) independently of how many sources or contexts a given
+/// implementation actually wires up; `SOURCES` and `CONTEXTS` only bound which source ids and
+/// context indices are valid for this instance and how far the context region extends, so the
+/// type maps exactly onto a vendor's window (SiFive, T-HEAD, the QEMU/Spike virt PLIC, ... all
+/// expose far fewer than the spec maximum). [`StandardPlic`] preserves the spec-maximum layout
+/// this crate exposed before these counts were generic.
+///
+/// `Plic` carries no fields of its own size, but the MMIO window it overlays is 4096-byte aligned
+/// by the RISC-V platform spec, so the type keeps that alignment.
 ///
 /// See §3.
 #[repr(C, align(4096))]
-pub struct Plic {
-    priorities: Priorities,
-    pending_bits: PendingBits,
-    _reserved0: [u8; 4096 - size_of::<PendingBits>()],
-    enables: Enables,
-    _reserved1: [u8; 0xe000],
-    context_local: [ContextLocal; COUNT_CONTEXT],
+pub struct Plic<const SOURCES: usize, const CONTEXTS: usize> {
+    base: UnsafeCell<()>,
 }
 
-impl Plic {
+/// The spec-maximum PLIC layout, i.e. the layout [`Plic`] exposed before it became generic over
+/// its source and context counts.
+pub type StandardPlic = Plic<1024, 15872>;
+
+impl<const SOURCES: usize, const CONTEXTS: usize> Plic<SOURCES, CONTEXTS> {
+    const PRIORITY_BASE: usize = 0x0000;
+    const PENDING_BASE: usize = 0x1000;
+    const ENABLE_BASE: usize = 0x2000;
+    const ENABLE_CONTEXT_STRIDE: usize = ENABLE_WORDS_PER_CONTEXT * size_of::<u32>();
+    const CONTEXT_BASE: usize = 0x20_0000;
+    const CONTEXT_STRIDE: usize = 0x1000;
+    const THRESHOLD_OFFSET: usize = 0;
+    const CLAIM_OR_COMPLETION_OFFSET: usize = size_of::<u32>();
+
+    /// Total size in bytes of the MMIO window this `SOURCES`/`CONTEXTS` configuration occupies.
+    ///
+    /// See §3.
+    pub const FOOTPRINT: usize = Self::CONTEXT_BASE + CONTEXTS * Self::CONTEXT_STRIDE;
+
+    /// Panics, in every build profile, if `id` does not name a source this PLIC instance wires up.
+    /// This is load-bearing in release builds too: without it, a safe call with an out-of-range id
+    /// would perform a wild MMIO access outside this PLIC's window instead of failing loudly.
+    #[inline]
+    fn check_source(id: NonZeroU32) -> usize {
+        let id = id.get() as usize;
+        assert!(id < SOURCES, "interrupt source out of range for this Plic");
+        id
+    }
+
+    /// Panics, in every build profile, if `index` does not name a context this PLIC instance wires
+    /// up. See [`Self::check_source`] for why this check cannot be debug-only.
+    #[inline]
+    fn check_context(index: usize) -> usize {
+        assert!(index < CONTEXTS, "hart context out of range for this Plic");
+        index
+    }
+
+    #[inline]
+    fn context_offset(context: usize) -> usize {
+        Self::CONTEXT_BASE + context * Self::CONTEXT_STRIDE
+    }
+
+    /// # Safety
+    ///
+    /// `offset` must be a valid, 4-byte-aligned register offset within this PLIC's MMIO window.
+    #[inline]
+    unsafe fn reg(&self, offset: usize) -> *mut u32 {
+        self.base.get().cast::<u8>().add(offset).cast()
+    }
+
     /// Sets priority for interrupt `source` to `value`.
     ///
     /// Write `0` to priority `value` effectively disables this interrupt `source`, for the priority
@@ -99,7 +186,8 @@ impl Plic {
     where
         S: InterruptSource,
     {
-        let ptr = self.priorities.0[source.id().get() as usize].get();
+        let id = Self::check_source(source.id());
+        let ptr = unsafe { self.reg(Self::PRIORITY_BASE + id * size_of::<u32>()) };
         unsafe { ptr.write_volatile(value) }
     }
 
@@ -111,7 +199,8 @@ impl Plic {
     where
         S: InterruptSource,
     {
-        let ptr = self.priorities.0[source.id().get() as usize].get();
+        let id = Self::check_source(source.id());
+        let ptr = unsafe { self.reg(Self::PRIORITY_BASE + id * size_of::<u32>()) };
         unsafe { ptr.read_volatile() }
     }
 
@@ -123,7 +212,8 @@ impl Plic {
     where
         S: InterruptSource,
     {
-        let ptr = self.priorities.0[source.id().get() as usize].get();
+        let id = Self::check_source(source.id());
+        let ptr = unsafe { self.reg(Self::PRIORITY_BASE + id * size_of::<u32>()) };
         unsafe {
             ptr.write_volatile(!0);
             ptr.read_volatile()
@@ -138,11 +228,11 @@ impl Plic {
     where
         S: InterruptSource,
     {
-        let source = source.id().get() as usize;
-        let group = source / U32_BITS;
-        let index = source % U32_BITS;
+        let id = Self::check_source(source.id());
+        let group = id / U32_BITS;
+        let index = id % U32_BITS;
 
-        let ptr = self.pending_bits.0[group].get();
+        let ptr = unsafe { self.reg(Self::PENDING_BASE + group * size_of::<u32>()) };
         (unsafe { ptr.read_volatile() } & (1 << index)) != 0
     }
 
@@ -155,13 +245,18 @@ impl Plic {
         S: InterruptSource,
         C: HartContext,
     {
-        let source = source.id().get() as usize;
-        let context = context.index();
-        let pos = context * COUNT_SOURCE + source;
-        let group = pos / U32_BITS;
-        let index = pos % U32_BITS;
+        let id = Self::check_source(source.id());
+        let context = Self::check_context(context.index());
+        let group = id / U32_BITS;
+        let index = id % U32_BITS;
 
-        let ptr = self.enables.0[group].get();
+        let ptr = unsafe {
+            self.reg(
+                Self::ENABLE_BASE
+                    + context * Self::ENABLE_CONTEXT_STRIDE
+                    + group * size_of::<u32>(),
+            )
+        };
         unsafe { ptr.write_volatile(ptr.read_volatile() | (1 << index)) }
     }
 
@@ -174,13 +269,18 @@ impl Plic {
         S: InterruptSource,
         C: HartContext,
     {
-        let source = source.id().get() as usize;
-        let context = context.index();
-        let pos = context * COUNT_SOURCE + source;
-        let group = pos / U32_BITS;
-        let index = pos % U32_BITS;
+        let id = Self::check_source(source.id());
+        let context = Self::check_context(context.index());
+        let group = id / U32_BITS;
+        let index = id % U32_BITS;
 
-        let ptr = self.enables.0[group].get();
+        let ptr = unsafe {
+            self.reg(
+                Self::ENABLE_BASE
+                    + context * Self::ENABLE_CONTEXT_STRIDE
+                    + group * size_of::<u32>(),
+            )
+        };
         unsafe { ptr.write_volatile(ptr.read_volatile() & !(1 << index)) }
     }
 
@@ -193,16 +293,136 @@ impl Plic {
         S: InterruptSource,
         C: HartContext,
     {
-        let source = source.id().get() as usize;
-        let context = context.index();
-        let pos = context * COUNT_SOURCE + source;
-        let group = pos / U32_BITS;
-        let index = pos % U32_BITS;
+        let id = Self::check_source(source.id());
+        let context = Self::check_context(context.index());
+        let group = id / U32_BITS;
+        let index = id % U32_BITS;
 
-        let ptr = self.enables.0[group].get();
+        let ptr = unsafe {
+            self.reg(
+                Self::ENABLE_BASE
+                    + context * Self::ENABLE_CONTEXT_STRIDE
+                    + group * size_of::<u32>(),
+            )
+        };
         (unsafe { ptr.read_volatile() } & (1 << index)) != 0
     }
 
+    #[inline]
+    unsafe fn enable_word_ptr(&self, context: usize, word_index: usize) -> *mut u32 {
+        assert!(
+            word_index < ENABLE_WORDS_PER_CONTEXT,
+            "enable word index out of range for this Plic"
+        );
+        unsafe {
+            self.reg(
+                Self::ENABLE_BASE
+                    + context * Self::ENABLE_CONTEXT_STRIDE
+                    + word_index * size_of::<u32>(),
+            )
+        }
+    }
+
+    /// Writes the whole enable word at `word_index` for `context` to `mask`, setting the
+    /// enablement of sources `word_index * 32 ..= word_index * 32 + 31` in one volatile write
+    /// instead of 32 single-bit read-modify-write round-trips.
+    ///
+    /// See §6.
+    #[inline]
+    pub fn set_enables<C>(&self, context: C, word_index: usize, mask: u32)
+    where
+        C: HartContext,
+    {
+        let context = Self::check_context(context.index());
+        let ptr = unsafe { self.enable_word_ptr(context, word_index) };
+        unsafe { ptr.write_volatile(mask) }
+    }
+
+    /// Reads the whole enable word at `word_index` for `context`, applies `f`, and writes the
+    /// result back in a single read-modify-write.
+    ///
+    /// See §6.
+    #[inline]
+    pub fn modify_enables<C>(&self, context: C, word_index: usize, f: impl FnOnce(u32) -> u32)
+    where
+        C: HartContext,
+    {
+        let context = Self::check_context(context.index());
+        let ptr = unsafe { self.enable_word_ptr(context, word_index) };
+        unsafe { ptr.write_volatile(f(ptr.read_volatile())) }
+    }
+
+    /// Enables every source yielded by `sources` in `context`, coalescing sources that fall in the
+    /// same enable word into a single read-modify-write instead of one per source.
+    ///
+    /// See §6.
+    pub fn enable_all<C, S>(&self, context: C, sources: S)
+    where
+        C: HartContext,
+        S: IntoIterator,
+        S::Item: InterruptSource,
+    {
+        let context = Self::check_context(context.index());
+        let mut masks = [0u32; ENABLE_WORDS_PER_CONTEXT];
+        for source in sources {
+            let id = Self::check_source(source.id());
+            masks[id / U32_BITS] |= 1 << (id % U32_BITS);
+        }
+        for (word_index, &mask) in masks.iter().enumerate() {
+            if mask != 0 {
+                let ptr = unsafe { self.enable_word_ptr(context, word_index) };
+                unsafe { ptr.write_volatile(ptr.read_volatile() | mask) }
+            }
+        }
+    }
+
+    /// Disables every source yielded by `sources` in `context`, coalescing sources that fall in
+    /// the same enable word into a single read-modify-write instead of one per source.
+    ///
+    /// See §6.
+    pub fn disable_all<C, S>(&self, context: C, sources: S)
+    where
+        C: HartContext,
+        S: IntoIterator,
+        S::Item: InterruptSource,
+    {
+        let context = Self::check_context(context.index());
+        let mut masks = [0u32; ENABLE_WORDS_PER_CONTEXT];
+        for source in sources {
+            let id = Self::check_source(source.id());
+            masks[id / U32_BITS] |= 1 << (id % U32_BITS);
+        }
+        for (word_index, &mask) in masks.iter().enumerate() {
+            if mask != 0 {
+                let ptr = unsafe { self.enable_word_ptr(context, word_index) };
+                unsafe { ptr.write_volatile(ptr.read_volatile() & !mask) }
+            }
+        }
+    }
+
+    /// Snapshots every source currently enabled in `context`, reading one volatile word per 32
+    /// sources instead of probing each source individually.
+    ///
+    /// See §6.
+    pub fn enabled_sources<C>(&self, context: C) -> impl Iterator<Item = NonZeroU32> + '_
+    where
+        C: HartContext,
+    {
+        let context = Self::check_context(context.index());
+        let words = SOURCES.div_ceil(U32_BITS).min(ENABLE_WORDS_PER_CONTEXT);
+        (0..words).flat_map(move |word_index| {
+            let ptr = unsafe { self.enable_word_ptr(context, word_index) };
+            let mask = unsafe { ptr.read_volatile() };
+            (0..U32_BITS).filter_map(move |bit| {
+                if mask & (1 << bit) == 0 {
+                    return None;
+                }
+                let id = word_index * U32_BITS + bit;
+                (id < SOURCES).then(|| NonZeroU32::new(id as u32)).flatten()
+            })
+        })
+    }
+
     /// Get interrupt threshold in `context`.
     ///
     /// See §7.
@@ -211,7 +431,8 @@ impl Plic {
     where
         C: HartContext,
     {
-        let ptr = self.context_local[context.index()].priority_threshold.get();
+        let context = Self::check_context(context.index());
+        let ptr = unsafe { self.reg(Self::context_offset(context) + Self::THRESHOLD_OFFSET) };
         unsafe { ptr.read_volatile() }
     }
 
@@ -223,7 +444,8 @@ impl Plic {
     where
         C: HartContext,
     {
-        let ptr = self.context_local[context.index()].priority_threshold.get();
+        let context = Self::check_context(context.index());
+        let ptr = unsafe { self.reg(Self::context_offset(context) + Self::THRESHOLD_OFFSET) };
         unsafe { ptr.write_volatile(value) }
     }
 
@@ -235,7 +457,8 @@ impl Plic {
     where
         C: HartContext,
     {
-        let ptr = self.context_local[context.index()].priority_threshold.get();
+        let context = Self::check_context(context.index());
+        let ptr = unsafe { self.reg(Self::context_offset(context) + Self::THRESHOLD_OFFSET) };
         unsafe {
             ptr.write_volatile(!0);
             ptr.read_volatile()
@@ -255,9 +478,9 @@ impl Plic {
     where
         C: HartContext,
     {
-        let ptr = self.context_local[context.index()]
-            .claim_or_completion
-            .get();
+        let context = Self::check_context(context.index());
+        let ptr =
+            unsafe { self.reg(Self::context_offset(context) + Self::CLAIM_OR_COMPLETION_OFFSET) };
         NonZeroU32::new(unsafe { ptr.read_volatile() })
     }
 
@@ -270,19 +493,416 @@ impl Plic {
         C: HartContext,
         S: InterruptSource,
     {
-        let ptr = self.context_local[context.index()]
-            .claim_or_completion
-            .get();
+        let context = Self::check_context(context.index());
+        let ptr =
+            unsafe { self.reg(Self::context_offset(context) + Self::CLAIM_OR_COMPLETION_OFFSET) };
         unsafe { ptr.write_volatile(source.id().get()) }
     }
+
+    /// Claim an interrupt in `context`, returning a guard that completes it automatically on drop.
+    ///
+    /// This pairs [`Plic::claim`] with a guaranteed [`Plic::complete`], so a forgotten completion
+    /// can no longer wedge the gateway for the claimed source. Use [`ClaimGuard::complete_now`] to
+    /// finalize early, or [`ClaimGuard::forget`] / [`ClaimGuard::into_inner`] to give up the source
+    /// without completing it, e.g. when the hart intends to poll for it again later.
+    ///
+    /// See §8, §9.
+    #[inline]
+    pub fn claim_guard<C>(&self, context: C) -> Option<ClaimGuard<'_, SOURCES, CONTEXTS, C>>
+    where
+        C: HartContext,
+    {
+        let context = Self::check_context(context.index());
+        let ptr =
+            unsafe { self.reg(Self::context_offset(context) + Self::CLAIM_OR_COMPLETION_OFFSET) };
+        NonZeroU32::new(unsafe { ptr.read_volatile() }).map(|source| ClaimGuard {
+            plic: self,
+            context,
+            source,
+            _context: PhantomData,
+        })
+    }
+
+    /// Builds a `Plic` handle over the MMIO window starting at `base`.
+    ///
+    /// # Safety
+    ///
+    /// `base` must point to a valid PLIC MMIO window of at least [`Plic::FOOTPRINT`] bytes that
+    /// implements at least `SOURCES` sources and `CONTEXTS` contexts, and must be aligned to
+    /// [`align_of::<Self>()`](align_of), i.e. 4096 bytes. The returned reference is `'static`
+    /// because a PLIC, like other MMIO peripherals, lives for the lifetime of the program.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base` is not 4096-byte aligned.
+    #[inline]
+    pub unsafe fn from_base(base: *mut ()) -> &'static Self {
+        assert!(
+            (base as usize).is_multiple_of(align_of::<Self>()),
+            "Plic base address is not 4096-byte aligned"
+        );
+        unsafe { &*base.cast() }
+    }
 }
 
-unsafe impl Sync for Plic {}
+unsafe impl<const SOURCES: usize, const CONTEXTS: usize> Sync for Plic<SOURCES, CONTEXTS> {}
+
+/// A PLIC context entry parsed from a `plic` device tree node's `interrupts-extended` property.
+///
+/// `phandle` is the referenced hart-local interrupt controller (its `riscv,cpu-intc` node) and
+/// `irq` is the local IRQ number it is wired to (conventionally `11` for machine-mode external and
+/// `9` for supervisor-mode external interrupts). The entry's position within the node is its PLIC
+/// context index, i.e. the value [`HartContext::index`] must return to reach it.
+///
+/// See §1.1.
+#[cfg(feature = "fdt")]
+#[derive(Clone, Copy, Debug)]
+pub struct FdtContext {
+    /// The interrupt controller phandle this context's claim line targets.
+    pub phandle: u32,
+    /// The local IRQ number on that controller.
+    pub irq: u32,
+}
+
+/// Number of `u32` cells each `interrupts-extended` entry occupies: a controller phandle plus the
+/// local IRQ number on it. This assumes the common one-cell `#interrupt-cells = <1>` convention for
+/// the referenced `riscv,cpu-intc` controllers (their only defined interrupt is the external line);
+/// a device tree using a different controller binding would need a different stride.
+#[cfg(feature = "fdt")]
+const FDT_CELLS_PER_CONTEXT: usize = 2;
+
+/// Lazily-decoded view over a `plic` device tree node's `interrupts-extended` property, indexed by
+/// PLIC context.
+///
+/// This borrows the property's raw bytes and decodes an entry only when [`Self::get`] or
+/// [`Self::iter`] asks for it, rather than materializing one [`FdtContext`] per context up front:
+/// for [`StandardPlic`] (`CONTEXTS` = 15872), eagerly building `[Option<FdtContext>; CONTEXTS]`
+/// would put ~186 KiB on the stack, a near-certain overflow on a kernel boot path.
+#[cfg(feature = "fdt")]
+#[derive(Clone, Copy, Debug)]
+pub struct FdtContexts<'dt> {
+    cells: &'dt [u8],
+}
+
+#[cfg(feature = "fdt")]
+impl FdtContexts<'_> {
+    /// Number of fully-present entries in the underlying property.
+    pub fn len(&self) -> usize {
+        self.cells.len() / (size_of::<u32>() * FDT_CELLS_PER_CONTEXT)
+    }
+
+    /// Whether the underlying property has no complete entry at all.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Decodes the entry at PLIC context `index`, or `None` if the property doesn't cover it.
+    pub fn get(&self, index: usize) -> Option<FdtContext> {
+        let cell_bytes = size_of::<u32>() * FDT_CELLS_PER_CONTEXT;
+        let offset = index * cell_bytes;
+        let entry = self.cells.get(offset..offset + cell_bytes)?;
+        Some(FdtContext {
+            phandle: u32::from_be_bytes(entry[0..4].try_into().unwrap()),
+            irq: u32::from_be_bytes(entry[4..8].try_into().unwrap()),
+        })
+    }
+
+    /// Iterates the entries in context order.
+    pub fn iter(&self) -> impl Iterator<Item = FdtContext> + '_ {
+        (0..self.len()).map(move |index| self.get(index).unwrap())
+    }
+}
+
+#[cfg(feature = "fdt")]
+impl<const SOURCES: usize, const CONTEXTS: usize> Plic<SOURCES, CONTEXTS> {
+    /// Builds a `Plic` handle from a `plic` device tree `node`, reading its base address from
+    /// `reg` and its implemented source count from `riscv,ndev`.
+    ///
+    /// Returns the handle, the parsed `ndev`, and a lazy view over the node's `interrupts-extended`
+    /// context table (see [`FdtContexts`]). Callers match a `(hart, privilege)` pair against the
+    /// controller phandle it expects to translate it into a [`HartContext`] index, instead of
+    /// hand-coding vendor offsets.
+    ///
+    /// `riscv,ndev` gives the number of implemented devices, i.e. the highest valid source id, so
+    /// this `Plic`'s `SOURCES` (a register count, satisfying [`Plic::check_source`]'s `id < SOURCES`)
+    /// must be at least `ndev + 1`, not merely `>= ndev`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node has no `reg` property, or if `riscv,ndev` leaves no room for its own
+    /// highest source id in this `Plic`, i.e. if `ndev >= SOURCES`.
+    ///
+    /// # Safety
+    ///
+    /// The node's `reg` address must point to a valid PLIC MMIO window, see [`Plic::from_base`].
+    pub unsafe fn from_fdt_node<'dt>(
+        node: fdt::node::FdtNode<'dt, '_>,
+    ) -> (&'static Self, usize, FdtContexts<'dt>) {
+        let base = node
+            .reg()
+            .and_then(|mut regs| regs.next())
+            .expect("plic node has no `reg` property")
+            .starting_address as *mut ();
+
+        let ndev = node
+            .property("riscv,ndev")
+            .and_then(|p| p.as_usize())
+            .unwrap_or(SOURCES - 1);
+        assert!(
+            ndev < SOURCES,
+            "riscv,ndev leaves no room for its highest source id in this Plic (SOURCES must be at least ndev + 1)"
+        );
+
+        let contexts = FdtContexts {
+            cells: node
+                .property("interrupts-extended")
+                .map(|property| property.value)
+                .unwrap_or(&[]),
+        };
+
+        (unsafe { Self::from_base(base) }, ndev, contexts)
+    }
+}
+
+/// RAII guard for a claimed interrupt source, obtained from [`Plic::claim_guard`].
+///
+/// Dropping the guard writes the claimed source id back to the context's `claim_or_completion`
+/// register, exactly mirroring [`Plic::complete`].
+pub struct ClaimGuard<'a, const SOURCES: usize, const CONTEXTS: usize, C: HartContext> {
+    plic: &'a Plic<SOURCES, CONTEXTS>,
+    context: usize,
+    source: NonZeroU32,
+    _context: PhantomData<C>,
+}
+
+impl<const SOURCES: usize, const CONTEXTS: usize, C: HartContext>
+    ClaimGuard<'_, SOURCES, CONTEXTS, C>
+{
+    /// The claimed interrupt source.
+    #[inline]
+    pub fn source(&self) -> NonZeroU32 {
+        self.source
+    }
+
+    /// Completes the claim immediately, consuming the guard.
+    #[inline]
+    pub fn complete_now(self) {
+        drop(self)
+    }
+
+    /// Releases the claimed source without completing it, consuming the guard.
+    #[inline]
+    pub fn forget(self) -> NonZeroU32 {
+        let source = self.source;
+        core::mem::forget(self);
+        source
+    }
+
+    /// Releases the claimed source without completing it, consuming the guard.
+    ///
+    /// Equivalent to [`ClaimGuard::forget`].
+    #[inline]
+    pub fn into_inner(self) -> NonZeroU32 {
+        self.forget()
+    }
+}
+
+impl<const SOURCES: usize, const CONTEXTS: usize, C: HartContext> core::ops::Deref
+    for ClaimGuard<'_, SOURCES, CONTEXTS, C>
+{
+    type Target = NonZeroU32;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.source
+    }
+}
+
+impl<const SOURCES: usize, const CONTEXTS: usize, C: HartContext> Drop
+    for ClaimGuard<'_, SOURCES, CONTEXTS, C>
+{
+    #[inline]
+    fn drop(&mut self) {
+        let ptr = unsafe {
+            self.plic.reg(
+                Plic::<SOURCES, CONTEXTS>::context_offset(self.context)
+                    + Plic::<SOURCES, CONTEXTS>::CLAIM_OR_COMPLETION_OFFSET,
+            )
+        };
+        unsafe { ptr.write_volatile(self.source.get()) }
+    }
+}
+
+/// A dispatch table mapping interrupt source ids to handlers, for `context`s of type `C`.
+///
+/// `N` bounds the highest source id this dispatcher can route; source ids `1..=N` are
+/// addressable. This turns the raw claim/complete registers into a drop-in external-interrupt
+/// service routine for S-mode kernels, replacing the hand-rolled "claim, look up handler, run it,
+/// complete" loop every consumer of this crate otherwise reimplements.
+pub struct Dispatcher<C: HartContext, const N: usize> {
+    handlers: [Option<fn(NonZeroU32)>; N],
+    _context: PhantomData<C>,
+}
+
+impl<C: HartContext, const N: usize> Dispatcher<C, N> {
+    /// Creates an empty dispatch table with no handlers registered.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            handlers: [None; N],
+            _context: PhantomData,
+        }
+    }
+
+    /// Registers `handler` to run when `source` is claimed, replacing any handler previously
+    /// registered for it.
+    ///
+    /// Panics if `source` is greater than `N`.
+    #[inline]
+    pub fn register<S>(&mut self, source: S, handler: fn(NonZeroU32))
+    where
+        S: InterruptSource,
+    {
+        self.handlers[source.id().get() as usize - 1] = Some(handler);
+    }
+
+    /// Deregisters the handler for `source`, if any.
+    ///
+    /// Panics if `source` is greater than `N`.
+    #[inline]
+    pub fn deregister<S>(&mut self, source: S)
+    where
+        S: InterruptSource,
+    {
+        self.handlers[source.id().get() as usize - 1] = None;
+    }
+
+    /// Drains every pending claim in `context`, running the registered handler for each claimed
+    /// source. Each claim is completed via [`ClaimGuard`]'s `Drop` as soon as its handler returns,
+    /// so an early return from a handler still leaves the source completed instead of wedging the
+    /// gateway. The guard is alive while the handler runs, so a panic that unwinds also completes
+    /// the claim; under `panic = "abort"` the process ends before `Drop` runs and the claim is not
+    /// completed.
+    ///
+    /// Sources with no registered handler are claimed and completed without running anything.
+    ///
+    /// See §8, §9.
+    pub fn handle<const SOURCES: usize, const CONTEXTS: usize>(
+        &self,
+        plic: &Plic<SOURCES, CONTEXTS>,
+        context: C,
+    ) where
+        C: Copy,
+    {
+        while let Some(guard) = plic.claim_guard(context) {
+            let source = guard.source();
+            if let Some(handler) = self
+                .handlers
+                .get(source.get() as usize - 1)
+                .copied()
+                .flatten()
+            {
+                handler(source);
+            }
+        }
+    }
+}
+
+impl<C: HartContext, const N: usize> Default for Dispatcher<C, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 #[test]
 fn test() {
+    assert_eq!(StandardPlic::FOOTPRINT, 0x20_0000 + 15872 * 0x1000)
+}
+
+#[cfg(test)]
+struct TestContext;
+
+#[cfg(test)]
+impl HartContext for TestContext {
+    fn index(self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+struct TestSource(u32);
+
+#[cfg(test)]
+impl InterruptSource for TestSource {
+    fn id(self) -> NonZeroU32 {
+        NonZeroU32::new(self.0).unwrap()
+    }
+}
+
+#[cfg(test)]
+fn with_test_plic<R>(f: impl FnOnce(&Plic<40, 2>) -> R) -> R {
+    #[repr(C, align(4096))]
+    struct Buf([u8; 0x3000]);
+
+    let buf = Buf([0u8; 0x3000]);
+    let plic = unsafe { &*(&buf as *const Buf).cast::<Plic<40, 2>>() };
+    f(plic)
+}
+
+#[test]
+fn enable_all_coalesces_and_enabled_sources_snapshots() {
+    with_test_plic(|plic| {
+        plic.enable_all(TestContext, [TestSource(1), TestSource(3), TestSource(33)]);
+        assert!(plic.enabled_sources(TestContext).eq([1, 3, 33]
+            .into_iter()
+            .map(|id| NonZeroU32::new(id).unwrap())));
+
+        plic.disable_all(TestContext, [TestSource(3)]);
+        assert!(plic
+            .enabled_sources(TestContext)
+            .eq([1, 33].into_iter().map(|id| NonZeroU32::new(id).unwrap())));
+    });
+}
+
+#[cfg(feature = "hart-priv")]
+#[test]
+fn hart_priv_index_is_hart_times_two_plus_mode() {
+    assert_eq!(
+        HartPriv {
+            hart: 0,
+            mode: Privilege::Machine
+        }
+        .index(),
+        0
+    );
     assert_eq!(
-        size_of::<Plic>(),
-        0x20_0000 + COUNT_CONTEXT * size_of::<ContextLocal>()
-    )
+        HartPriv {
+            hart: 0,
+            mode: Privilege::Supervisor
+        }
+        .index(),
+        1
+    );
+    assert_eq!(
+        HartPriv {
+            hart: 3,
+            mode: Privilege::Supervisor
+        }
+        .index(),
+        7
+    );
+}
+
+#[test]
+fn dispatcher_register_maps_source_id_to_zero_based_slot() {
+    fn handler(_: NonZeroU32) {}
+
+    let mut dispatcher = Dispatcher::<TestContext, 4>::new();
+    assert!(dispatcher.handlers.iter().all(Option::is_none));
+
+    dispatcher.register(TestSource(3), handler);
+    assert!(dispatcher.handlers[2].is_some());
+
+    dispatcher.deregister(TestSource(3));
+    assert!(dispatcher.handlers[2].is_none());
 }